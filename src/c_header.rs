@@ -0,0 +1,284 @@
+//! Renders a reflected [`Type`](../enum.Type.html) back out as a C `struct`/scalar
+//! declaration, the way a debugger's pretty-printer renders its IR back to readable text.
+//! The generated declarations are guaranteed ABI-compatible with the original Rust
+//! layout: gaps between consecutive fields' offsets, and any trailing padding up to the
+//! struct's size, are filled in with explicit `uint8_t` padding fields, and an `Enum` whose variants
+//! carry a payload is rendered as an opaque `uint8_t[N]` sized to its full value,
+//! since its payload has no single, representable C member.
+
+use {Type, Variant, VariantFields};
+
+/// Title-cases a field name to use as the name of a struct hoisted from that field,
+/// e.g. `"colors"` becomes `"Colors"`. This is only a starting point: two different
+/// field shapes that happen to share a field name are disambiguated further by
+/// [`resolve_struct_name`].
+fn struct_name_for(field_name: &str) -> String {
+    let mut chars = field_name.chars();
+    match chars.next() {
+        Some(c) => c.to_uppercase().collect::<String>() + chars.as_str(),
+        None => "Anon".to_string(),
+    }
+}
+
+/// Field names synthesized for a `Tuple`'s anonymous fields: `_0`, `_1`, ...
+fn tuple_field_name(index: usize) -> String {
+    format!("_{}", index)
+}
+
+/// Whether any variant carries a payload, in which case the enum's true size can
+/// exceed that of its bare discriminant and it can no longer be rendered as one.
+fn has_payload(variants: &[Variant]) -> bool {
+    variants.iter().any(|v| v.fields != VariantFields::Unit)
+}
+
+/// Resolves the struct name to hoist `ty` (a `Compound` or `Tuple`) under, given the
+/// name of the field it came from. Two occurrences of the exact same shape under the
+/// same field name share one hoisted struct, as before; but two occurrences that
+/// share a field name while having a *different* shape no longer collide on one
+/// struct body — the dedup key is the field layout, not the bare field name, so the
+/// second shape is disambiguated with a numeric suffix (`Colors`, `Colors2`, ...)
+/// instead of silently reusing (and shadowing) the first struct's body.
+///
+/// Returns the resolved name, and whether this is a new shape that still needs its
+/// body rendered (`false` if this exact shape was already seen under this name).
+fn resolve_struct_name(field_name: &str, ty: &Type, seen: &mut Vec<(String, Type)>) -> (String, bool) {
+    let base = struct_name_for(field_name);
+    let mut candidate = base.clone();
+    let mut suffix = 2;
+    loop {
+        match seen.iter().find(|&&(ref name, _)| *name == candidate) {
+            Some(&(_, ref existing)) if existing == ty => return (candidate, false),
+            Some(_) => {
+                candidate = format!("{}{}", base, suffix);
+                suffix += 1;
+            },
+            None => {
+                seen.push((candidate.clone(), ty.clone()));
+                return (candidate, true);
+            },
+        }
+    }
+}
+
+/// Renders a pointer declaration (`{qualifier}{pointee_keyword} *name{dims}`), shared
+/// by `to_c_decl` and `render_member_decl`'s `Ptr`/array-of-`Ptr` handling. A pointer's
+/// pointee is always rendered as an opaque scalar-or-`void` keyword via
+/// [`c_pointee_keyword`](Type::c_pointee_keyword) — never hoisted as a struct body, even
+/// when it's itself a `Compound`/`Tuple` — so this needs no `out`/`seen` state.
+fn render_ptr_decl(pointee: &Type, mutable: bool, name: &str, dims: &[usize]) -> String {
+    let qualifier = if mutable { "" } else { "const " };
+    let dims: String = dims.iter().map(|n| format!("[{}]", n)).collect();
+    format!("{}{} *{}{}", qualifier, pointee.c_pointee_keyword(), name, dims)
+}
+
+impl Type {
+    /// Renders the C base type keyword for a value of this type that would be
+    /// named `field_name` if it were hoisted into its own struct (used for
+    /// `Compound`/`Tuple`/`Enum`, which have no name of their own).
+    fn c_keyword(&self, field_name: &str) -> String {
+        match *self {
+            Type::Int8 => "int8_t".to_string(),
+            Type::Int16 => "int16_t".to_string(),
+            Type::Int32 => "int32_t".to_string(),
+            Type::Int64 => "int64_t".to_string(),
+            Type::UInt8 => "uint8_t".to_string(),
+            Type::UInt16 => "uint16_t".to_string(),
+            Type::UInt32 => "uint32_t".to_string(),
+            Type::UInt64 => "uint64_t".to_string(),
+            Type::Float32 => "float".to_string(),
+            Type::Float64 => "double".to_string(),
+            Type::Bool => "bool".to_string(),
+            // `char` is a 4-byte Unicode scalar value, not a C `char`
+            Type::Char => "uint32_t".to_string(),
+            Type::Compound(..) | Type::Tuple(..) => format!("struct {}", struct_name_for(field_name)),
+            // a fieldless enum's size equals its discriminant's, so the discriminant's
+            // keyword is an exact fit; payload-carrying enums never reach this arm, see
+            // `c_keyword_and_extra_dim` below
+            Type::Enum { ref discriminant, .. } => discriminant.c_keyword(field_name),
+            Type::Array(ref ty, _) => ty.c_keyword(field_name),
+            // not reached by the renderers in this module: pointers are rendered
+            // directly by `render_ptr_decl`
+            Type::Ptr { .. } => "void".to_string(),
+        }
+    }
+
+    /// Renders the C type keyword to use for the pointee of a `*const`/`*mut` field;
+    /// anything that isn't a plain scalar is treated as opaque (`void`).
+    fn c_pointee_keyword(&self) -> String {
+        if self.is_scalar() {
+            self.c_keyword("")
+        } else {
+            "void".to_string()
+        }
+    }
+
+    /// Like [`c_keyword`](#method.c_keyword), but also accounts for payload-carrying
+    /// enums: since their payload has no representation as a plain C member, they're
+    /// rendered as an opaque `uint8_t[N]` byte array sized to the enum's true
+    /// [`size`](#method.size) rather than its discriminant alone, so that gap/offset
+    /// math for any field that follows it in a `struct` still lines up with the real
+    /// Rust layout. The returned dimension, when present, must be appended to
+    /// whatever array dimensions the caller is already building up.
+    fn c_keyword_and_extra_dim(&self, field_name: &str) -> (String, Option<usize>) {
+        if let Type::Enum { ref variants, size, .. } = *self {
+            if has_payload(variants) {
+                return ("uint8_t".to_string(), Some(size));
+            }
+        }
+        (self.c_keyword(field_name), None)
+    }
+
+    /// Renders `self` as a single C declaration for a field or variable named `name`,
+    /// e.g. `int32_t count` or `float colors[16]`. Does not emit the bodies of any
+    /// `struct`s it references, and does not deduplicate hoisted struct names against
+    /// any other declaration — use [`to_c_decls`](#method.to_c_decls) for that.
+    pub fn to_c_decl(&self, name: &str) -> String {
+        match *self {
+            Type::Array(ref ty, num) => {
+                let mut dims = vec![num];
+                let mut inner = ty.as_ref();
+                while let Type::Array(ref ity, inum) = *inner {
+                    dims.push(inum);
+                    inner = ity;
+                }
+                // an array of pointers needs the dedicated `Ptr` rendering below, not
+                // `c_keyword_and_extra_dim`'s generic fallback (which would render
+                // the pointer element as a bare `void`, dropping its `*`)
+                if let Type::Ptr { ref pointee, mutable } = *inner {
+                    return render_ptr_decl(pointee, mutable, name, &dims);
+                }
+                let (keyword, extra) = inner.c_keyword_and_extra_dim(name);
+                if let Some(extra_dim) = extra {
+                    dims.push(extra_dim);
+                }
+                let dims: String = dims.iter().map(|n| format!("[{}]", n)).collect();
+                format!("{} {}{}", keyword, name, dims)
+            },
+            Type::Ptr { ref pointee, mutable } => render_ptr_decl(pointee, mutable, name, &[]),
+            _ => {
+                let (keyword, extra) = self.c_keyword_and_extra_dim(name);
+                match extra {
+                    Some(n) => format!("{} {}[{}]", keyword, name, n),
+                    None => format!("{} {}", keyword, name),
+                }
+            },
+        }
+    }
+
+    /// Renders the full set of `struct` declarations `self` depends on (every nested
+    /// `Compound`/`Tuple`, hoisted into its own named struct and emitted in
+    /// definition order so each struct is declared before anything that embeds it),
+    /// followed by the declaration of `self` itself as `name`. Explicit
+    /// `uint8_t _padN[k];` fields are inserted wherever there's a gap between a
+    /// field's offset and the end of the previous one, and to fill the struct's
+    /// trailing padding, so each struct matches the Rust layout byte-for-byte.
+    ///
+    /// Two nested fields with the same name but a different shape are hoisted into
+    /// two distinctly-named structs rather than colliding on one (see
+    /// [`resolve_struct_name`]).
+    pub fn to_c_decls(&self, name: &str) -> String {
+        let mut decls = Vec::new();
+        let mut seen = Vec::new();
+        let decl = self.render_member_decl(name, &mut decls, &mut seen);
+        let mut out = decls;
+        out.push(format!("{};", decl));
+        out.join("\n\n")
+    }
+
+    /// Like [`to_c_decl`](#method.to_c_decl), but hoists any nested `Compound`/
+    /// `Tuple` struct bodies into `out` as it goes, resolving each one's name
+    /// through `seen` so that differently-shaped fields sharing a field name don't
+    /// collide (see [`resolve_struct_name`]).
+    fn render_member_decl(&self, name: &str, out: &mut Vec<String>, seen: &mut Vec<(String, Type)>) -> String {
+        match *self {
+            Type::Array(ref ty, num) => {
+                let mut dims = vec![num];
+                let mut inner = ty.as_ref();
+                while let Type::Array(ref ity, inum) = *inner {
+                    dims.push(inum);
+                    inner = ity;
+                }
+                // never hoist the pointee's struct body: `render_ptr_decl` renders it
+                // as an opaque scalar-or-`void` keyword, so a hoisted body here would
+                // never be referenced by anything in `out` (see `render_ptr_decl`)
+                if let Type::Ptr { ref pointee, mutable } = *inner {
+                    return render_ptr_decl(pointee, mutable, name, &dims);
+                }
+                let (keyword, extra) = inner.render_keyword(name, out, seen);
+                if let Some(extra_dim) = extra {
+                    dims.push(extra_dim);
+                }
+                let dims: String = dims.iter().map(|n| format!("[{}]", n)).collect();
+                format!("{} {}{}", keyword, name, dims)
+            },
+            // see the note in the `Array`/`Ptr` arm above: the pointee is never hoisted
+            Type::Ptr { ref pointee, mutable } => render_ptr_decl(pointee, mutable, name, &[]),
+            _ => {
+                let (keyword, extra) = self.render_keyword(name, out, seen);
+                match extra {
+                    Some(n) => format!("{} {}[{}]", keyword, name, n),
+                    None => format!("{} {}", keyword, name),
+                }
+            },
+        }
+    }
+
+    /// Like [`c_keyword_and_extra_dim`](#method.c_keyword_and_extra_dim), but for
+    /// `Compound`/`Tuple` hoists the struct body into `out` (via `render_member_decl`
+    /// for each field, so nesting is hoisted too), naming and deduplicating it
+    /// through `seen` instead of blindly trusting the field name to be unique.
+    fn render_keyword(&self, field_name: &str, out: &mut Vec<String>, seen: &mut Vec<(String, Type)>) -> (String, Option<usize>) {
+        match *self {
+            Type::Compound(ref fields, size, _) => {
+                let members: Vec<(String, usize, usize)> = fields.iter()
+                    .map(|f| (f.ty.render_member_decl(&f.name, out, seen), f.offset, f.ty.size()))
+                    .collect();
+                let (struct_name, is_new) = resolve_struct_name(field_name, self, seen);
+                if is_new {
+                    out.push(render_struct(&struct_name, &members, size));
+                }
+                (format!("struct {}", struct_name), None)
+            },
+            Type::Tuple(ref fields, size, _) => {
+                let members: Vec<(String, usize, usize)> = fields.iter().enumerate()
+                    .map(|(i, f)| (f.ty.render_member_decl(&tuple_field_name(i), out, seen), f.offset, f.ty.size()))
+                    .collect();
+                let (struct_name, is_new) = resolve_struct_name(field_name, self, seen);
+                if is_new {
+                    out.push(render_struct(&struct_name, &members, size));
+                }
+                (format!("struct {}", struct_name), None)
+            },
+            Type::Enum { ref variants, size, .. } if has_payload(variants) => {
+                ("uint8_t".to_string(), Some(size))
+            },
+            Type::Array(ref ty, _) => ty.render_keyword(field_name, out, seen),
+            _ => (self.c_keyword(field_name), None),
+        }
+    }
+}
+
+/// Renders a single `struct Name { ... };` body from its already-rendered member
+/// declarations, laid out in offset order with explicit `uint8_t` padding fields
+/// filling any gaps.
+fn render_struct(struct_name: &str, members: &[(String, usize, usize)], size: usize) -> String {
+    let mut members: Vec<_> = members.to_vec();
+    members.sort_by_key(|&(_, offset, _)| offset);
+
+    let mut lines = Vec::new();
+    let mut prev_end = 0;
+    let mut pad_index = 0;
+    for &(ref decl, offset, member_size) in &members {
+        if offset > prev_end {
+            lines.push(format!("    uint8_t _pad{}[{}];", pad_index, offset - prev_end));
+            pad_index += 1;
+        }
+        lines.push(format!("    {};", decl));
+        prev_end = offset + member_size;
+    }
+    if size > prev_end {
+        lines.push(format!("    uint8_t _pad{}[{}];", pad_index, size - prev_end));
+    }
+
+    format!("struct {} {{\n{}\n}};", struct_name, lines.join("\n"))
+}
@@ -51,14 +51,19 @@
 //!                     NamedField { ty: UInt16, name: "r", offset: 0 },
 //!                     NamedField { ty: UInt16, name: "g", offset: 2 },
 //!                     NamedField { ty: UInt16, name: "b", offset: 4 }
-//!                 ], 6),
+//!                 ], 6, 2),
 //!             16),
 //!         name: "colors",
 //!         offset: 1
 //!     }
-//! ], 97)
+//! ], 97, 1)
 //! ```
 
+mod value;
+mod c_header;
+
+pub use value::{Endian, Value};
+
 /// Represents a POD type: scalar, fixed-size array or compound (struct).
 /// May be arbitrarily nested.
 #[derive(Clone, PartialEq, Debug)]
@@ -89,10 +94,30 @@ pub enum Type {
     Bool,
     /// fixed-size array with POD elements
     Array(Box<Type>, usize),
-    /// compound type whose fields are POD
-    Compound(Vec<NamedField>, usize),
-    /// tuple or a tuple struct with POD elements
-    Tuple(Vec<Field>, usize),
+    /// compound type whose fields are POD; the `usize`s are size and alignment
+    Compound(Vec<NamedField>, usize, usize),
+    /// tuple or a tuple struct with POD elements; the `usize`s are size and alignment
+    Tuple(Vec<Field>, usize, usize),
+    /// C-style enum with an explicit discriminant and a set of variants, each
+    /// of which may itself carry a unit, tuple or struct payload
+    Enum {
+        /// the variants, in declaration order
+        variants: Vec<Variant>,
+        /// scalar type used to store the discriminant (picked from `#[repr(..)]`)
+        discriminant: Box<Type>,
+        /// total size of the enum value in bytes, discriminant and payload included
+        size: usize,
+        /// alignment of the enum value in bytes
+        align: usize,
+    },
+    /// raw pointer or reference; carries no ownership or lifetime information,
+    /// just the pointee type and whether it was `mut`
+    Ptr {
+        /// type being pointed to
+        pointee: Box<Type>,
+        /// whether the pointer or reference is mutable
+        mutable: bool,
+    },
 }
 
 impl Type {
@@ -104,14 +129,55 @@ impl Type {
             Type::Int32 | Type::UInt32 | Type::Float32 | Type::Char => 4,
             Type::Int64 | Type::UInt64 | Type::Float64 => 8,
             Type::Array(ref ty, num) => ty.size() * num,
-            Type::Compound(_, size) |
-            Type::Tuple(_, size) => size,
+            Type::Compound(_, size, _) |
+            Type::Tuple(_, size, _) => size,
+            Type::Enum { size, .. } => size,
+            Type::Ptr { .. } => ::std::mem::size_of::<usize>(),
+        }
+    }
+
+    /// Returns the alignment of a type value in bytes.
+    pub fn align(&self) -> usize {
+        match *self {
+            Type::Int8 | Type::UInt8 | Type::Bool => 1,
+            Type::Int16 | Type::UInt16 => 2,
+            Type::Int32 | Type::UInt32 | Type::Float32 | Type::Char => 4,
+            Type::Int64 | Type::UInt64 | Type::Float64 => 8,
+            Type::Array(ref ty, _) => ty.align(),
+            Type::Compound(_, _, align) |
+            Type::Tuple(_, _, align) => align,
+            Type::Enum { align, .. } => align,
+            Type::Ptr { .. } => ::std::mem::align_of::<usize>(),
         }
     }
 
+    /// Returns the total number of inter-field and trailing padding bytes in a
+    /// compound or tuple type, computed by walking its fields in offset order.
+    /// Always zero for scalars, arrays and enums.
+    pub fn padding_bytes(&self) -> usize {
+        let (mut spans, size) = match *self {
+            Type::Compound(ref fields, size, _) => {
+                (fields.iter().map(|f| (f.offset, f.ty.size())).collect::<Vec<_>>(), size)
+            },
+            Type::Tuple(ref fields, size, _) => {
+                (fields.iter().map(|f| (f.offset, f.ty.size())).collect::<Vec<_>>(), size)
+            },
+            _ => return 0,
+        };
+        spans.sort_by_key(|&(offset, _)| offset);
+        let mut prev_end = 0;
+        let mut padding = 0;
+        for (offset, field_size) in spans {
+            padding += offset - prev_end;
+            prev_end = offset + field_size;
+        }
+        padding + (size - prev_end)
+    }
+
     /// Returns true if the underlying type is a scalar.
     pub fn is_scalar(&self) -> bool {
-        !self.is_array() && !self.is_compound() && !self.is_tuple()
+        !self.is_array() && !self.is_compound() && !self.is_tuple() && !self.is_enum() &&
+            !self.is_pointer()
     }
 
     /// Returns true if the underlying type is a fixed-size array.
@@ -125,7 +191,7 @@ impl Type {
 
     /// Returns true if the underlying type is compound.
     pub fn is_compound(&self) -> bool {
-        if let Type::Compound(_, _) = *self {
+        if let Type::Compound(_, _, _) = *self {
             true
         } else {
             false
@@ -134,7 +200,25 @@ impl Type {
 
     /// Returns true if the underlying type is a tuple or a tuple struct.
     pub fn is_tuple(&self) -> bool {
-        if let Type::Tuple(_, _) = *self {
+        if let Type::Tuple(_, _, _) = *self {
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns true if the underlying type is a C-style enum.
+    pub fn is_enum(&self) -> bool {
+        if let Type::Enum { .. } = *self {
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns true if the underlying type is a raw pointer or a reference.
+    pub fn is_pointer(&self) -> bool {
+        if let Type::Ptr { .. } = *self {
             true
         } else {
             false
@@ -180,6 +264,40 @@ impl Field {
         }
     }
 }
+
+/// Payload carried by a single enum variant.
+#[derive(Clone, PartialEq, Debug)]
+pub enum VariantFields {
+    /// unit variant, carries no payload
+    Unit,
+    /// tuple variant, fields are anonymous and offsets are relative to the enum value
+    Tuple(Vec<Field>),
+    /// struct variant, fields are named and offsets are relative to the enum value
+    Struct(Vec<NamedField>),
+}
+
+/// A single variant of an [`Enum`](enum.Type.html#variant.Enum): its name, its
+/// discriminant value and its payload, if any.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Variant {
+    /// variant name
+    pub name: String,
+    /// discriminant value assigned to this variant
+    pub discriminant: i64,
+    /// variant payload, empty for unit variants
+    pub fields: VariantFields,
+}
+
+impl Variant {
+    pub fn new<S: Into<String>>(name: S, discriminant: i64, fields: VariantFields) -> Variant {
+        Variant {
+            name: name.into(),
+            discriminant: discriminant,
+            fields: fields,
+        }
+    }
+}
+
 /// Trait implemented by copyable POD data types with fixed size, enables
 /// runtime reflection.
 ///
@@ -196,6 +314,34 @@ pub trait TypeInfo: Copy {
     fn type_info() -> Type;
 }
 
+/// Marker for types whose all-zero-bytes bit pattern is always a valid value.
+///
+/// `def!` and `#[derive(TypeInfo)]` use this to synthesize a placeholder instance
+/// of each payload field of an enum variant, purely to locate that field's offset
+/// (the placeholder is never read, only addressed through a `ref` binding). Bounding
+/// that placeholder construction on `ZeroPod` turns what would otherwise be
+/// undefined behavior for a field type with no valid zero value — a reference,
+/// which can never be null, or a nested enum whose zero discriminant isn't one of
+/// its declared variants — into a compile error instead.
+///
+/// Implemented for every scalar, for raw pointers (a null pointer is always valid),
+/// and for arrays of `ZeroPod` elements. Not implemented for references, nor
+/// automatically for enums, since a zero discriminant isn't guaranteed to name one
+/// of their declared variants. Neither `def!` nor `#[derive(TypeInfo)]` implements
+/// it for the structs and tuple structs they define; such a type needs a
+/// hand-written `unsafe impl` to be usable as a payload field.
+///
+/// # Safety
+///
+/// Implementing this trait asserts that `mem::zeroed::<Self>()` never produces an
+/// invalid bit pattern.
+pub unsafe trait ZeroPod: TypeInfo {}
+
+#[doc(hidden)]
+pub fn __zero_pod_placeholder<T: ZeroPod>() -> T {
+    unsafe { ::std::mem::zeroed() }
+}
+
 macro_rules! impl_scalar {
     ($t:ty, $i:ident) => (
         impl $crate::TypeInfo for $t {
@@ -204,6 +350,7 @@ macro_rules! impl_scalar {
                 $crate::Type::$i
             }
         }
+        unsafe impl $crate::ZeroPod for $t {}
     )
 }
 
@@ -231,6 +378,34 @@ impl_scalar!(usize, UInt32);
 #[cfg(target_pointer_width = "64")]
 impl_scalar!(usize, UInt64);
 
+// implement TypeInfo for raw pointers and shared references; `&mut T` is intentionally
+// not covered since it isn't `Copy` and therefore can't satisfy the `TypeInfo` supertrait
+impl<T: TypeInfo> TypeInfo for *const T {
+    #[inline(always)]
+    fn type_info() -> Type {
+        Type::Ptr { pointee: Box::new(<T as TypeInfo>::type_info()), mutable: false }
+    }
+}
+
+// a null raw pointer is always a valid value, unlike a null reference
+unsafe impl<T: TypeInfo> ZeroPod for *const T {}
+
+impl<T: TypeInfo> TypeInfo for *mut T {
+    #[inline(always)]
+    fn type_info() -> Type {
+        Type::Ptr { pointee: Box::new(<T as TypeInfo>::type_info()), mutable: true }
+    }
+}
+
+unsafe impl<T: TypeInfo> ZeroPod for *mut T {}
+
+impl<'a, T: TypeInfo> TypeInfo for &'a T {
+    #[inline(always)]
+    fn type_info() -> Type {
+        Type::Ptr { pointee: Box::new(<T as TypeInfo>::type_info()), mutable: false }
+    }
+}
+
 macro_rules! impl_array {
     ($($n:expr),*$(,)*) => {
         $(
@@ -243,6 +418,7 @@ macro_rules! impl_array {
                     )
                 }
             }
+            unsafe impl<T: $crate::ZeroPod> $crate::ZeroPod for [T; $n] {}
         )*
     };
 }
@@ -273,6 +449,10 @@ impl_array! {
 /// *Note:* due to certain limitations of the macro system, a single macro invocation may
 /// only contain definitions where both fields and structs have the same visibility qualifier.
 ///
+/// `def!` also accepts C-style enums, in which case it additionally derives the
+/// discriminant scalar type from a leading `#[repr(..)]` attribute (defaulting to `i32`
+/// if none is given) and reflects unit, tuple and struct variants alike.
+///
 /// # Examples
 /// ```ignore
 /// def! {
@@ -291,6 +471,32 @@ impl_array! {
 /// ```
 #[macro_export]
 macro_rules! def {
+    // private enum, explicit `#[repr(..)]` (must come first among the attributes)
+    ($(#[repr($dt:ident)] $(#[$attr:meta])* enum $e:ident { $($body:tt)* })*) => (
+        $(
+            #[repr($dt)] $(#[$attr])* enum $e { $($body)* }
+            def!(@impl_enum $e $dt { $($body)* });
+        )*
+    );
+
+    // public enum, explicit `#[repr(..)]` (must come first among the attributes)
+    ($(#[repr($dt:ident)] $(#[$attr:meta])* pub enum $e:ident { $($body:tt)* })*) => (
+        $(
+            #[repr($dt)] $(#[$attr])* pub enum $e { $($body)* }
+            def!(@impl_enum $e $dt { $($body)* });
+        )*
+    );
+
+    // private enum, no explicit repr (discriminant defaults to `i32`)
+    ($($(#[$attr:meta])* enum $e:ident { $($body:tt)* })*) => (
+        $($(#[$attr])* enum $e { $($body)* } def!(@impl_enum $e i32 { $($body)* });)*
+    );
+
+    // public enum, no explicit repr (discriminant defaults to `i32`)
+    ($($(#[$attr:meta])* pub enum $e:ident { $($body:tt)* })*) => (
+        $($(#[$attr])* pub enum $e { $($body)* } def!(@impl_enum $e i32 { $($body)* });)*
+    );
+
     // private unit struct
     ($($(#[$attr:meta])* struct $s:ident);+$(;)*) => (
         $($(#[$attr])* struct $s; def!(@impl_struct $s { });)*
@@ -352,9 +558,17 @@ macro_rules! def {
                         stringify!($i),
                         unsafe { &((*(0usize as *const $s)).$i) as *const _ as usize }
                     )
-                ),*], ::std::mem::size_of::<$s>())
+                ),*], ::std::mem::size_of::<$s>(), ::std::mem::align_of::<$s>())
             }
         }
+
+        // `$s` is intentionally not made `ZeroPod` here: a `where $t: ZeroPod` bound
+        // on a concrete impl like this one is checked eagerly against `$s`'s actual
+        // fields, so it would reject this whole `def!` block as soon as any field
+        // (e.g. an enum, or a reference) isn't `ZeroPod`, regardless of whether `$s`
+        // is ever used as an enum variant's payload field. If `$s` needs to be usable
+        // as a payload field, add `unsafe impl typeinfo::ZeroPod for $s {}` next to
+        // it once you've checked its all-zero bit pattern is actually valid.
     );
 
     (@replace_with $a:tt $b:tt) => ($b);
@@ -377,9 +591,142 @@ macro_rules! def {
                 let origin = 0usize as *const $s;
                 let mut fields = Vec::<$crate::Field>::new();
                 def!(@parse_tuple_fields [$s] origin fields | $($tt),*);
-                $crate::Type::Tuple(fields, ::std::mem::size_of::<$s>())
+                $crate::Type::Tuple(fields, ::std::mem::size_of::<$s>(), ::std::mem::align_of::<$s>())
+            }
+        }
+
+        // see the note in `@impl_struct` on why `$s` isn't made `ZeroPod` here
+    );
+
+    // bounded on `ZeroPod` so that a field type with no valid zero value (e.g. a
+    // reference, or a nested enum whose zero discriminant isn't a declared variant)
+    // is rejected at compile time instead of producing an invalid value
+    (@zeroed $t:ty) => ($crate::__zero_pod_placeholder::<$t>());
+
+    // implement TypeInfo trait for enums
+    (@impl_enum $e:ident $dt:ident { $($body:tt)* }) => (
+        impl $crate::TypeInfo for $e {
+            #[allow(dead_code, unused_variables, unused_mut)]
+            fn type_info() -> $crate::Type {
+                let ty_size = ::std::mem::size_of::<$e>();
+                let mut variants = Vec::<$crate::Variant>::new();
+                let mut next_discriminant: i64 = 0;
+                def!(@parse_variants $e variants next_discriminant | $($body)*);
+                $crate::Type::Enum {
+                    variants: variants,
+                    discriminant: Box::new(<$dt as $crate::TypeInfo>::type_info()),
+                    size: ty_size,
+                    align: ::std::mem::align_of::<$e>(),
+                }
+            }
+        }
+    );
+
+    // end of variant list
+    (@parse_variants $e:ident $variants:ident $disc:ident |) => ();
+
+    // unit variant with an explicit discriminant, no trailing comma
+    (@parse_variants $e:ident $variants:ident $disc:ident | $v:ident = $d:expr) => (
+        def!(@parse_variants $e $variants $disc | $v = $d,);
+    );
+
+    // unit variant with an explicit discriminant
+    (@parse_variants $e:ident $variants:ident $disc:ident | $v:ident = $d:expr, $($rest:tt)*) => (
+        $disc = ($d) as i64;
+        $variants.push($crate::Variant::new(stringify!($v), $disc, $crate::VariantFields::Unit));
+        $disc += 1;
+        def!(@parse_variants $e $variants $disc | $($rest)*);
+    );
+
+    // struct variant, no trailing comma
+    (@parse_variants $e:ident $variants:ident $disc:ident |
+     $v:ident { $($i:ident: $t:ty),+$(,)* }) => (
+        def!(@parse_variants $e $variants $disc | $v { $($i: $t),+ },);
+    );
+
+    // struct variant
+    (@parse_variants $e:ident $variants:ident $disc:ident |
+     $v:ident { $($i:ident: $t:ty),+$(,)* }, $($rest:tt)*) => (
+        def!(@impl_variant_struct $e $v $variants $disc $($i: $t),+);
+        def!(@parse_variants $e $variants $disc | $($rest)*);
+    );
+
+    // tuple variant, no trailing comma
+    (@parse_variants $e:ident $variants:ident $disc:ident | $v:ident ($($t:ty),+$(,)*)) => (
+        def!(@parse_variants $e $variants $disc | $v($($t),+),);
+    );
+
+    // tuple variant
+    (@parse_variants $e:ident $variants:ident $disc:ident |
+     $v:ident ($($t:ty),+$(,)*), $($rest:tt)*) => (
+        def!(@impl_variant_tuple $e $v $variants $disc $($t),+);
+        def!(@parse_variants $e $variants $disc | $($rest)*);
+    );
+
+    // unit variant, no trailing comma
+    (@parse_variants $e:ident $variants:ident $disc:ident | $v:ident) => (
+        def!(@parse_variants $e $variants $disc | $v,);
+    );
+
+    // unit variant
+    (@parse_variants $e:ident $variants:ident $disc:ident | $v:ident, $($rest:tt)*) => (
+        $variants.push($crate::Variant::new(stringify!($v), $disc, $crate::VariantFields::Unit));
+        $disc += 1;
+        def!(@parse_variants $e $variants $disc | $($rest)*);
+    );
+
+    // construct an instance of a tuple variant, with each payload field placed via
+    // `@zeroed` (which requires `$t: ZeroPod`), and locate each field's offset
+    // relative to that instance, the same way `@parse_tuple_fields` does for tuple structs
+    (@impl_variant_tuple $e:ident $v:ident $variants:ident $disc:ident $($t:ty),+) => (
+        {
+            let value: $e = $e::$v($(def!(@zeroed $t)),+);
+            let origin = &value;
+            let base = origin as *const _ as usize;
+            let mut fields = Vec::<$crate::Field>::new();
+            def!(@parse_variant_tuple_fields $e $v origin fields base | $($t),+);
+            $variants.push($crate::Variant::new(stringify!($v), $disc, $crate::VariantFields::Tuple(fields)));
+        }
+        $disc += 1;
+    );
+
+    (@parse_variant_tuple_fields $e:ident $v:ident $origin:ident $fields:ident $base:ident |
+     $t:ty $(,$tt:ty)*) => (
+        match $origin {
+            &$e::$v(.., ref f, $(def!(@replace_with $tt _),)*) => {
+                $fields.push($crate::Field::new(
+                    &<$t as $crate::TypeInfo>::type_info(),
+                    (f as *const _ as usize) - $base));
             }
+            _ => unreachable!(),
         }
+        def!(@parse_variant_tuple_fields $e $v $origin $fields $base | $($tt),*);
+    );
+
+    (@parse_variant_tuple_fields $e:ident $v:ident $origin:ident $fields:ident $base:ident |) => ();
+
+    // construct an instance of a struct variant, with each payload field placed via
+    // `@zeroed` (which requires `$t: ZeroPod`), and locate each field's offset
+    // relative to that instance
+    (@impl_variant_struct $e:ident $v:ident $variants:ident $disc:ident $($i:ident: $t:ty),+) => (
+        {
+            let value: $e = $e::$v { $($i: def!(@zeroed $t)),+ };
+            let base = &value as *const _ as usize;
+            let mut fields = Vec::<$crate::NamedField>::new();
+            match value {
+                $e::$v { $(ref $i),+ } => {
+                    $(
+                        fields.push($crate::NamedField::new(
+                            &<$t as $crate::TypeInfo>::type_info(),
+                            stringify!($i),
+                            ($i as *const _ as usize) - base));
+                    )+
+                }
+                _ => unreachable!(),
+            }
+            $variants.push($crate::Variant::new(stringify!($v), $disc, $crate::VariantFields::Struct(fields)));
+        }
+        $disc += 1;
     );
 }
 
@@ -394,14 +741,15 @@ macro_rules! impl_tuple {
                     vec![$crate::Field::new(
                         &<$t as $crate::TypeInfo>::type_info(),
                         f as *const _ as usize)],
-                    ::std::mem::size_of::<($t,)>())
+                    ::std::mem::size_of::<($t,)>(),
+                    ::std::mem::align_of::<($t,)>())
             }
         }
 
         impl $crate::TypeInfo for () {
             #[inline(always)]
             fn type_info() -> $crate::Type {
-                $crate::Type::Tuple(vec![], ::std::mem::size_of::<()>())
+                $crate::Type::Tuple(vec![], ::std::mem::size_of::<()>(), ::std::mem::align_of::<()>())
             }
         }
     };
@@ -414,7 +762,10 @@ macro_rules! impl_tuple {
                 let origin = 0usize as *const ($t, $($tt),*);
                 let mut fields = Vec::<$crate::Field>::new();
                 def!(@parse_tuple_fields [] origin fields | $t, $($tt),*);
-                $crate::Type::Tuple(fields, ::std::mem::size_of::<($t, $($tt),*)>())
+                $crate::Type::Tuple(
+                    fields,
+                    ::std::mem::size_of::<($t, $($tt),*)>(),
+                    ::std::mem::align_of::<($t, $($tt),*)>())
             }
         }
 
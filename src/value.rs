@@ -0,0 +1,297 @@
+//! Endianness-aware interpretation of raw byte buffers according to a [`Type`](../enum.Type.html),
+//! analogous to how a debugger or an allocation reader decodes scalars for a given target.
+
+use Type;
+use VariantFields;
+
+/// Byte order used by [`Type::read`](../enum.Type.html#method.read) and
+/// [`Type::write`](../enum.Type.html#method.write) to interpret multi-byte scalars.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Endian {
+    /// least significant byte first
+    Little,
+    /// most significant byte first
+    Big,
+}
+
+impl Endian {
+    /// Returns the endianness of the host platform.
+    pub fn native() -> Endian {
+        if cfg!(target_endian = "big") {
+            Endian::Big
+        } else {
+            Endian::Little
+        }
+    }
+}
+
+/// A value tree mirroring [`Type`](../enum.Type.html): scalar leaves, arrays, and
+/// named (`Compound`) or anonymous (`Tuple`) field maps. Produced by
+/// [`Type::read`](../enum.Type.html#method.read) and consumed by
+/// [`Type::write`](../enum.Type.html#method.write).
+#[derive(Clone, PartialEq, Debug)]
+pub enum Value {
+    /// 1-byte signed integer
+    Int8(i8),
+    /// 2-byte signed integer
+    Int16(i16),
+    /// 4-byte signed integer
+    Int32(i32),
+    /// 8-byte signed integer
+    Int64(i64),
+    /// 1-byte unsigned integer
+    UInt8(u8),
+    /// 2-byte unsigned integer
+    UInt16(u16),
+    /// 4-byte unsigned integer
+    UInt32(u32),
+    /// 8-byte unsigned integer
+    UInt64(u64),
+    /// 4-byte floating-point number
+    Float32(f32),
+    /// 8-byte floating-point number
+    Float64(f64),
+    /// 4-byte unicode character
+    Char(char),
+    /// 1-byte boolean
+    Bool(bool),
+    /// fixed-size array of element values
+    Array(Vec<Value>),
+    /// compound value: named field values, in the same order as the `Type`
+    Compound(Vec<(String, Value)>),
+    /// tuple or tuple struct value: anonymous field values, in the same order as the `Type`
+    Tuple(Vec<Value>),
+    /// enum value: the matched variant's name together with its payload
+    /// (`Tuple([])` for unit variants, `Tuple(..)` for tuple variants, `Compound(..)`
+    /// for struct variants)
+    Enum {
+        /// name of the matched variant
+        variant: String,
+        /// variant payload
+        payload: Box<Value>,
+    },
+    /// raw pointer or reference value, kept as an opaque address rather than a
+    /// plain integer so consumers can tell pointer-sized fields from plain integers
+    Ptr(usize),
+}
+
+fn get_uint(bytes: &[u8], width: usize, endian: Endian) -> u64 {
+    let mut v: u64 = 0;
+    match endian {
+        Endian::Little => {
+            for i in (0..width).rev() {
+                v = (v << 8) | bytes[i] as u64;
+            }
+        },
+        Endian::Big => {
+            for i in 0..width {
+                v = (v << 8) | bytes[i] as u64;
+            }
+        },
+    }
+    v
+}
+
+fn put_uint(buf: &mut [u8], width: usize, value: u64, endian: Endian) {
+    match endian {
+        Endian::Little => {
+            for i in 0..width {
+                buf[i] = ((value >> (8 * i)) & 0xff) as u8;
+            }
+        },
+        Endian::Big => {
+            for i in 0..width {
+                buf[i] = ((value >> (8 * (width - 1 - i))) & 0xff) as u8;
+            }
+        },
+    }
+}
+
+/// Builds the scalar `Value` used to hold an enum discriminant of the given type.
+fn discriminant_value(ty: &Type, disc: i64) -> Value {
+    match *ty {
+        Type::Int8 => Value::Int8(disc as i8),
+        Type::Int16 => Value::Int16(disc as i16),
+        Type::Int32 => Value::Int32(disc as i32),
+        Type::Int64 => Value::Int64(disc),
+        Type::UInt8 => Value::UInt8(disc as u8),
+        Type::UInt16 => Value::UInt16(disc as u16),
+        Type::UInt32 => Value::UInt32(disc as u32),
+        Type::UInt64 => Value::UInt64(disc as u64),
+        _ => panic!("enum discriminant type must be an integer scalar"),
+    }
+}
+
+/// Reads the `i64` stored in a scalar discriminant `Value` back out.
+fn discriminant_of(value: &Value) -> i64 {
+    match *value {
+        Value::Int8(v) => v as i64,
+        Value::Int16(v) => v as i64,
+        Value::Int32(v) => v as i64,
+        Value::Int64(v) => v,
+        Value::UInt8(v) => v as i64,
+        Value::UInt16(v) => v as i64,
+        Value::UInt32(v) => v as i64,
+        Value::UInt64(v) => v as i64,
+        _ => panic!("enum discriminant value must be an integer scalar"),
+    }
+}
+
+impl Type {
+    /// Interprets `bytes` according to `self`, decoding scalars with the given
+    /// endianness. `bytes` must hold exactly `self.size()` bytes, with nested
+    /// fields expected at their stored offsets; padding gaps are skipped.
+    pub fn read(&self, bytes: &[u8], endian: Endian) -> Value {
+        match *self {
+            Type::Int8 => Value::Int8(bytes[0] as i8),
+            Type::UInt8 => Value::UInt8(bytes[0]),
+            Type::Bool => Value::Bool(bytes[0] != 0),
+            Type::Int16 => Value::Int16(get_uint(bytes, 2, endian) as i16),
+            Type::UInt16 => Value::UInt16(get_uint(bytes, 2, endian) as u16),
+            Type::Int32 => Value::Int32(get_uint(bytes, 4, endian) as i32),
+            Type::UInt32 => Value::UInt32(get_uint(bytes, 4, endian) as u32),
+            Type::Float32 => Value::Float32(f32_from_bits(get_uint(bytes, 4, endian) as u32)),
+            Type::Char => {
+                let c = get_uint(bytes, 4, endian) as u32;
+                Value::Char(::std::char::from_u32(c).unwrap_or('\u{fffd}'))
+            },
+            Type::Int64 => Value::Int64(get_uint(bytes, 8, endian) as i64),
+            Type::UInt64 => Value::UInt64(get_uint(bytes, 8, endian)),
+            Type::Float64 => Value::Float64(f64_from_bits(get_uint(bytes, 8, endian))),
+            Type::Array(ref ty, num) => {
+                let stride = ty.size();
+                let items = (0..num)
+                    .map(|i| ty.read(&bytes[i * stride..(i + 1) * stride], endian))
+                    .collect();
+                Value::Array(items)
+            },
+            Type::Compound(ref fields, _, _) => {
+                let values = fields.iter()
+                    .map(|f| (f.name.clone(), f.ty.read(&bytes[f.offset..f.offset + f.ty.size()], endian)))
+                    .collect();
+                Value::Compound(values)
+            },
+            Type::Tuple(ref fields, _, _) => {
+                let values = fields.iter()
+                    .map(|f| f.ty.read(&bytes[f.offset..f.offset + f.ty.size()], endian))
+                    .collect();
+                Value::Tuple(values)
+            },
+            Type::Enum { ref variants, ref discriminant, .. } => {
+                let disc_value = discriminant.read(&bytes[0..discriminant.size()], endian);
+                let disc = discriminant_of(&disc_value);
+                let variant = variants.iter().find(|v| v.discriminant == disc)
+                    .expect("no enum variant matches the stored discriminant");
+                let payload = match variant.fields {
+                    VariantFields::Unit => Value::Tuple(vec![]),
+                    VariantFields::Tuple(ref fields) => {
+                        Value::Tuple(fields.iter()
+                            .map(|f| f.ty.read(&bytes[f.offset..f.offset + f.ty.size()], endian))
+                            .collect())
+                    },
+                    VariantFields::Struct(ref fields) => {
+                        Value::Compound(fields.iter()
+                            .map(|f| (f.name.clone(), f.ty.read(&bytes[f.offset..f.offset + f.ty.size()], endian)))
+                            .collect())
+                    },
+                };
+                Value::Enum { variant: variant.name.clone(), payload: Box::new(payload) }
+            },
+            Type::Ptr { .. } => {
+                let width = ::std::mem::size_of::<usize>();
+                Value::Ptr(get_uint(bytes, width, endian) as usize)
+            },
+        }
+    }
+
+    /// Encodes `value` according to `self` into `out`, appending exactly `self.size()`
+    /// bytes, zero-filling any padding gaps. `write(read(bytes))` round-trips for
+    /// well-formed buffers, since both sides place fields at their stored offsets
+    /// rather than packing them sequentially.
+    pub fn write(&self, value: &Value, out: &mut Vec<u8>, endian: Endian) {
+        let start = out.len();
+        out.resize(start + self.size(), 0);
+        let buf = &mut out[start..];
+        self.write_into(value, buf, endian);
+    }
+
+    fn write_into(&self, value: &Value, buf: &mut [u8], endian: Endian) {
+        match (self, value) {
+            (&Type::Int8, &Value::Int8(v)) => buf[0] = v as u8,
+            (&Type::UInt8, &Value::UInt8(v)) => buf[0] = v,
+            (&Type::Bool, &Value::Bool(v)) => buf[0] = v as u8,
+            (&Type::Int16, &Value::Int16(v)) => put_uint(buf, 2, v as u16 as u64, endian),
+            (&Type::UInt16, &Value::UInt16(v)) => put_uint(buf, 2, v as u64, endian),
+            (&Type::Int32, &Value::Int32(v)) => put_uint(buf, 4, v as u32 as u64, endian),
+            (&Type::UInt32, &Value::UInt32(v)) => put_uint(buf, 4, v as u64, endian),
+            (&Type::Float32, &Value::Float32(v)) => put_uint(buf, 4, f32_to_bits(v) as u64, endian),
+            (&Type::Char, &Value::Char(v)) => put_uint(buf, 4, v as u64, endian),
+            (&Type::Int64, &Value::Int64(v)) => put_uint(buf, 8, v as u64, endian),
+            (&Type::UInt64, &Value::UInt64(v)) => put_uint(buf, 8, v, endian),
+            (&Type::Float64, &Value::Float64(v)) => put_uint(buf, 8, f64_to_bits(v), endian),
+            (&Type::Array(ref ty, num), &Value::Array(ref items)) => {
+                assert_eq!(items.len(), num, "array value has the wrong number of elements");
+                let stride = ty.size();
+                for (i, item) in items.iter().enumerate() {
+                    ty.write_into(item, &mut buf[i * stride..(i + 1) * stride], endian);
+                }
+            },
+            (&Type::Compound(ref fields, _, _), &Value::Compound(ref kvs)) => {
+                for (f, &(_, ref v)) in fields.iter().zip(kvs.iter()) {
+                    let end = f.offset + f.ty.size();
+                    f.ty.write_into(v, &mut buf[f.offset..end], endian);
+                }
+            },
+            (&Type::Tuple(ref fields, _, _), &Value::Tuple(ref items)) => {
+                for (f, v) in fields.iter().zip(items.iter()) {
+                    let end = f.offset + f.ty.size();
+                    f.ty.write_into(v, &mut buf[f.offset..end], endian);
+                }
+            },
+            (&Type::Enum { ref variants, ref discriminant, .. },
+             &Value::Enum { ref variant, ref payload }) => {
+                let v = variants.iter().find(|v| &v.name == variant)
+                    .expect("unknown enum variant name");
+                let disc_value = discriminant_value(discriminant, v.discriminant);
+                let disc_size = discriminant.size();
+                discriminant.write_into(&disc_value, &mut buf[0..disc_size], endian);
+                match (&v.fields, payload.as_ref()) {
+                    (&VariantFields::Unit, &Value::Tuple(ref items)) if items.is_empty() => {},
+                    (&VariantFields::Tuple(ref fields), &Value::Tuple(ref items)) => {
+                        for (f, v) in fields.iter().zip(items.iter()) {
+                            let end = f.offset + f.ty.size();
+                            f.ty.write_into(v, &mut buf[f.offset..end], endian);
+                        }
+                    },
+                    (&VariantFields::Struct(ref fields), &Value::Compound(ref kvs)) => {
+                        for (f, &(_, ref v)) in fields.iter().zip(kvs.iter()) {
+                            let end = f.offset + f.ty.size();
+                            f.ty.write_into(v, &mut buf[f.offset..end], endian);
+                        }
+                    },
+                    _ => panic!("value payload does not match the variant's fields"),
+                }
+            },
+            (&Type::Ptr { .. }, &Value::Ptr(addr)) => {
+                put_uint(buf, ::std::mem::size_of::<usize>(), addr as u64, endian);
+            },
+            _ => panic!("value does not match type"),
+        }
+    }
+}
+
+fn f32_to_bits(v: f32) -> u32 {
+    v.to_bits()
+}
+
+fn f32_from_bits(v: u32) -> f32 {
+    f32::from_bits(v)
+}
+
+fn f64_to_bits(v: f64) -> u64 {
+    v.to_bits()
+}
+
+fn f64_from_bits(v: u64) -> f64 {
+    f64::from_bits(v)
+}
@@ -4,7 +4,7 @@ extern crate typeinfo;
 use std::mem;
 
 use typeinfo::Type::*;
-use typeinfo::{Type, TypeInfo, Field, NamedField};
+use typeinfo::{Type, TypeInfo, Field, NamedField, VariantFields};
 
 #[test]
 fn test_scalar() {
@@ -53,13 +53,16 @@ fn test_array() {
 fn test_tuple() {
     let ty = <(i8, u32) as TypeInfo>::type_info();
     let size = mem::size_of::<(i8, u32)>();
-    assert_eq!(ty, Tuple(vec![Field::new(&Int8, 0), Field::new(&UInt32, 4)], size));
+    let align = mem::align_of::<(i8, u32)>();
+    assert_eq!(ty, Tuple(vec![Field::new(&Int8, 0), Field::new(&UInt32, 4)], size, align));
     assert_eq!(ty.size(), size);
+    assert_eq!(ty.align(), align);
     assert!(ty.is_tuple());
 
     let ty = <() as TypeInfo>::type_info();
-    assert_eq!(ty, Tuple(vec![], 0));
+    assert_eq!(ty, Tuple(vec![], 0, 1));
     assert_eq!(ty.size(), 0);
+    assert_eq!(ty.align(), 1);
     assert!(ty.is_tuple());
 }
 
@@ -67,19 +70,23 @@ fn test_tuple() {
 fn test_tuple_struct() {
     def![#[derive(Clone, Copy)] struct X(bool, i64)];
     let ty = X::type_info();
-    assert_eq!(ty, Tuple(vec![Field::new(&Bool, 0), Field::new(&Int64, 8)], 16));
+    assert_eq!(ty, Tuple(vec![Field::new(&Bool, 0), Field::new(&Int64, 8)], 16, 8));
     assert_eq!(ty.size(), 16);
+    assert_eq!(ty.align(), 8);
+    assert_eq!(ty.padding_bytes(), 7);
     assert!(ty.is_tuple());
 
     def![#[repr(packed)] #[derive(Clone, Copy)] struct P(bool, i64)];
     let ty = P::type_info();
-    assert_eq!(ty, Tuple(vec![Field::new(&Bool, 0), Field::new(&Int64, 1)], 9));
+    assert_eq!(ty, Tuple(vec![Field::new(&Bool, 0), Field::new(&Int64, 1)], 9, 1));
     assert_eq!(ty.size(), 9);
+    assert_eq!(ty.align(), 1);
+    assert_eq!(ty.padding_bytes(), 0);
     assert!(ty.is_tuple());
 
     def![#[derive(Clone, Copy)] struct Y()];
     let ty = Y::type_info();
-    assert_eq!(ty, Tuple(vec![], 0));
+    assert_eq!(ty, Tuple(vec![], 0, 1));
     assert_eq!(ty.size(), 0);
     assert!(ty.is_tuple());
 }
@@ -90,8 +97,10 @@ fn test_compound() {
     let ty = X::type_info();
     assert_eq!(ty, Compound(vec![
         NamedField::new(&Int32, "a", 0)
-    ], mem::size_of::<X>()));
+    ], mem::size_of::<X>(), mem::align_of::<X>()));
     assert_eq!(ty.size(), mem::size_of::<X>());
+    assert_eq!(ty.align(), mem::align_of::<X>());
+    assert_eq!(ty.padding_bytes(), 0);
     assert!(ty.is_compound());
 
     def![#[derive(Clone, Copy)] struct Y { a: u64, x: [X; 2] }];
@@ -99,15 +108,270 @@ fn test_compound() {
     assert_eq!(ty, Compound(vec![
         NamedField::new(&UInt64, "a", 0),
         NamedField::new(&Array(Box::new(X::type_info()), 2), "x", 8),
-    ], mem::size_of::<Y>()));
+    ], mem::size_of::<Y>(), mem::align_of::<Y>()));
     assert_eq!(ty.size(), mem::size_of::<Y>());
+    assert_eq!(ty.align(), mem::align_of::<Y>());
     assert!(ty.is_compound());
 
     def![#[derive(Clone, Copy)] struct Z];
     let ty = Z::type_info();
-    assert_eq!(ty, Compound(vec![], mem::size_of::<Z>()));
+    assert_eq!(ty, Compound(vec![], mem::size_of::<Z>(), mem::align_of::<Z>()));
     assert_eq!(ty.size(), mem::size_of::<Z>());
     assert!(ty.is_compound());
+
+    def![#[repr(packed)] #[derive(Clone, Copy)] struct W { a: i8, b: i64 }];
+    let ty = W::type_info();
+    assert_eq!(ty.align(), 1);
+    assert_eq!(ty.padding_bytes(), 0);
+}
+
+#[test]
+fn test_enum() {
+    def! {
+        #[repr(u8)]
+        #[derive(Clone, Copy)]
+        enum Color {
+            Red,
+            Green = 5,
+            Blue,
+        }
+    }
+    let ty = Color::type_info();
+    if let Enum { ref variants, ref discriminant, size, align } = ty {
+        assert_eq!(**discriminant, UInt8);
+        assert_eq!(size, mem::size_of::<Color>());
+        assert_eq!(align, mem::align_of::<Color>());
+        assert_eq!(variants.len(), 3);
+        assert_eq!(variants[0].name, "Red");
+        assert_eq!(variants[0].discriminant, 0);
+        assert_eq!(variants[1].name, "Green");
+        assert_eq!(variants[1].discriminant, 5);
+        assert_eq!(variants[2].name, "Blue");
+        assert_eq!(variants[2].discriminant, 6);
+    } else {
+        panic!("expected Type::Enum");
+    }
+    assert!(ty.is_enum());
+    assert!(!ty.is_scalar());
+
+    def! {
+        #[repr(u8)]
+        #[derive(Clone, Copy)]
+        enum Shape {
+            Point,
+            Circle(f32),
+            Rect { w: f32, h: f32 },
+        }
+    }
+    let ty = Shape::type_info();
+    if let Enum { ref variants, .. } = ty {
+        assert_eq!(variants[0].fields, VariantFields::Unit);
+        match variants[1].fields {
+            VariantFields::Tuple(ref fields) => {
+                assert_eq!(fields.len(), 1);
+                assert_eq!(fields[0].ty, Float32);
+            },
+            _ => panic!("expected a tuple variant"),
+        }
+        match variants[2].fields {
+            VariantFields::Struct(ref fields) => {
+                assert_eq!(fields.len(), 2);
+                assert_eq!(fields[0].name, "w");
+                assert_eq!(fields[1].name, "h");
+            },
+            _ => panic!("expected a struct variant"),
+        }
+    } else {
+        panic!("expected Type::Enum");
+    }
+}
+
+#[test]
+fn test_pointer() {
+    let ty = <*const u8 as TypeInfo>::type_info();
+    assert_eq!(ty, Ptr { pointee: Box::new(UInt8), mutable: false });
+    assert_eq!(ty.size(), mem::size_of::<usize>());
+    assert!(ty.is_pointer());
+    assert!(!ty.is_scalar());
+
+    let ty = <*mut i32 as TypeInfo>::type_info();
+    assert_eq!(ty, Ptr { pointee: Box::new(Int32), mutable: true });
+
+    let ty = <&u8 as TypeInfo>::type_info();
+    assert_eq!(ty, Ptr { pointee: Box::new(UInt8), mutable: false });
+
+    def![#[derive(Clone, Copy)] struct Buf { data: *const u8, len: usize }];
+    let ty = Buf::type_info();
+    assert_eq!(ty, Compound(vec![
+        NamedField::new(&Ptr { pointee: Box::new(UInt8), mutable: false }, "data", 0),
+        NamedField::new(&<usize as TypeInfo>::type_info(), "len", mem::size_of::<usize>()),
+    ], mem::size_of::<Buf>(), mem::align_of::<Buf>()));
+}
+
+#[test]
+fn test_read_write_roundtrip() {
+    use typeinfo::{Endian, Value};
+
+    def![#[derive(Clone, Copy)] struct X { a: u16, b: i8 }];
+    let ty = X::type_info();
+    let value = Value::Compound(vec![
+        ("a".into(), Value::UInt16(0x0203)),
+        ("b".into(), Value::Int8(-1)),
+    ]);
+
+    let mut bytes = Vec::new();
+    ty.write(&value, &mut bytes, Endian::Little);
+    assert_eq!(bytes.len(), ty.size());
+    assert_eq!(ty.read(&bytes, Endian::Little), value);
+
+    let mut bytes_be = Vec::new();
+    ty.write(&value, &mut bytes_be, Endian::Big);
+    assert_eq!(ty.read(&bytes_be, Endian::Big), value);
+    assert_ne!(bytes, bytes_be);
+
+    def! {
+        #[repr(u8)]
+        #[derive(Clone, Copy)]
+        enum Tagged {
+            Empty,
+            Single(u32),
+        }
+    }
+    let ty = Tagged::type_info();
+    let value = Value::Enum {
+        variant: "Single".into(),
+        payload: Box::new(Value::Tuple(vec![Value::UInt32(0xdead_beef)])),
+    };
+    let mut bytes = Vec::new();
+    ty.write(&value, &mut bytes, Endian::Little);
+    assert_eq!(bytes.len(), ty.size());
+    assert_eq!(ty.read(&bytes, Endian::Little), value);
+
+    let ty = <*const u8 as TypeInfo>::type_info();
+    let value = Value::Ptr(0xdead_beef);
+    let mut bytes = Vec::new();
+    ty.write(&value, &mut bytes, Endian::Little);
+    assert_eq!(ty.read(&bytes, Endian::Little), value);
+}
+
+#[test]
+fn test_c_decl() {
+    assert_eq!(Int32.to_c_decl("count"), "int32_t count");
+    assert_eq!(Float64.to_c_decl("x"), "double x");
+    assert_eq!(Array(Box::new(UInt16), 16).to_c_decl("colors"), "uint16_t colors[16]");
+
+    let ptr = Ptr { pointee: Box::new(UInt8), mutable: false };
+    assert_eq!(ptr.to_c_decl("data"), "const uint8_t *data");
+
+    def! {
+        #[derive(Clone, Copy)]
+        struct Color { r: u16, g: u16, b: u16 }
+    }
+    def! {
+        #[derive(Clone, Copy)]
+        #[repr(packed)]
+        struct Palette { monochrome: bool, colors: [Color; 16] }
+    }
+    let ty = Palette::type_info();
+    let header = ty.to_c_decls("Palette");
+    assert!(header.contains("struct Colors {"));
+    assert!(header.contains("uint16_t r;"));
+    assert!(header.contains("struct Palette {"));
+    assert!(header.contains("bool monochrome;"));
+    assert!(header.contains("struct Colors colors[16];"));
+    // the `struct Colors` body must come before the `struct Palette` body that embeds it
+    assert!(header.find("struct Colors {").unwrap() < header.find("struct Palette {").unwrap());
+
+    def! {
+        #[derive(Clone, Copy)]
+        struct Misaligned { a: i8, b: i64 }
+    }
+    let ty = Misaligned::type_info();
+    let header = ty.to_c_decls("Misaligned");
+    assert!(header.contains("_pad0[7];"));
+
+    // a payload-carrying enum must be rendered as an opaque byte array sized to its
+    // *whole* value, not just its discriminant, or the field after it in the
+    // generated struct would land at the wrong offset
+    def! {
+        #[derive(Clone, Copy)]
+        #[repr(u8)]
+        enum Tag {
+            Empty,
+            Number(f64),
+        }
+    }
+    def! {
+        #[derive(Clone, Copy)]
+        struct Tagged { tag: Tag, after: u8 }
+    }
+    let tag_size = mem::size_of::<Tag>();
+    let ty = Tagged::type_info();
+    let header = ty.to_c_decls("Tagged");
+    assert!(header.contains(&format!("uint8_t tag[{}];", tag_size)));
+    // `after`'s offset in the rendered struct must match its real offset in `Tagged`
+    if let Compound(ref fields, ..) = ty {
+        let after_offset = fields.iter().find(|f| f.name == "after").unwrap().offset;
+        if after_offset > tag_size {
+            assert!(header.contains(&format!("_pad0[{}];", after_offset - tag_size)));
+        } else {
+            assert_eq!(after_offset, tag_size);
+        }
+    } else {
+        panic!("expected Type::Compound");
+    }
+
+    // an array of pointers must keep the pointer's `*`/`const` qualifier, not
+    // collapse to an opaque, incomplete `void` element
+    let ptr_array = Array(Box::new(Ptr { pointee: Box::new(UInt8), mutable: false }), 4);
+    assert_eq!(ptr_array.to_c_decl("argv"), "const uint8_t *argv[4]");
+
+    // a pointer to a compound type is rendered as opaque `void *`, so its pointee's
+    // struct body must never be hoisted into the header — it would be dead, unreferenced
+    let inner_ty = Compound(
+        vec![NamedField::new(&Int32, "v", 0)],
+        4, 4,
+    );
+    let ptr_to_compound = Compound(
+        vec![NamedField::new(&Ptr { pointee: Box::new(inner_ty), mutable: false }, "items", 0)],
+        mem::size_of::<usize>(), mem::size_of::<usize>(),
+    );
+    let header = ptr_to_compound.to_c_decls("WithPtr");
+    assert!(!header.contains("struct Items"));
+    assert!(header.contains("const void *items;"));
+
+    // two differently-shaped fields that happen to share a field name must be
+    // hoisted into two distinctly-named structs, not collide on one
+    def! {
+        #[derive(Clone, Copy)]
+        struct Inner1 { v: i32 }
+    }
+    def! {
+        #[derive(Clone, Copy)]
+        struct Inner2 { v: i64, w: i64 }
+    }
+    def! {
+        #[derive(Clone, Copy)]
+        struct Wrap1 { data: Inner1 }
+    }
+    def! {
+        #[derive(Clone, Copy)]
+        struct Top { data: Inner2, wrapped: Wrap1 }
+    }
+    let ty = Top::type_info();
+    let header = ty.to_c_decls("Top");
+    assert!(header.contains("struct Data {"));
+    assert!(header.contains("struct Data2 {"));
+    // `Data` (from `Top.data: Inner2`) must keep Inner2's real layout...
+    let data_body = &header[header.find("struct Data {").unwrap()..header.find("struct Data2").unwrap()];
+    assert!(data_body.contains("int64_t v;"));
+    assert!(data_body.contains("int64_t w;"));
+    // ...while `Data2` (from `Wrap1.data: Inner1`) keeps Inner1's, not Inner2's
+    let data2_body = &header[header.find("struct Data2 {").unwrap()..header.find("struct Wrapped").unwrap()];
+    assert!(data2_body.contains("int32_t v;"));
+    assert!(!data2_body.contains("int64_t"));
+    // `Wrapped.data` must reference the struct that actually matches its shape
+    assert!(header.contains("struct Data2 data;"));
 }
 
 #[test]
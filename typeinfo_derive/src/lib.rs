@@ -9,7 +9,7 @@ extern crate syn;
 extern crate typeinfo;
 
 use proc_macro::TokenStream;
-use syn::{Body, VariantData};
+use syn::{Attribute, Body, Ident, MetaItem, VariantData};
 
 #[proc_macro_derive(TypeInfo)]
 pub fn type_info(input: TokenStream) -> TokenStream {
@@ -17,12 +17,13 @@ pub fn type_info(input: TokenStream) -> TokenStream {
     let ast = syn::parse_macro_input(&s).unwrap();
     let name = &ast.ident;
     let (impl_gen, ty_gen, where_clause) = ast.generics.split_for_impl();
-    let body = type_info_impl(&ast.body);
+    let body = type_info_impl(name, &ast.attrs, &ast.body);
     let gen = quote! {
         #[allow(dead_code, unused_variables)]
         impl #impl_gen ::typeinfo::TypeInfo for #name #ty_gen #where_clause {
             fn type_info() -> ::typeinfo::Type {
                 let ty_size = ::std::mem::size_of::<#name>();
+                let ty_align = ::std::mem::align_of::<#name>();
                 let origin = 0usize as *const #name;
                 #body
             }
@@ -31,10 +32,41 @@ pub fn type_info(input: TokenStream) -> TokenStream {
     gen.parse().unwrap()
 }
 
-fn type_info_impl(body: &Body) -> quote::Tokens {
+// Deriving `ZeroPod` for `#name` here isn't possible: a `where FieldTy: ZeroPod`
+// bound on a concrete impl like `impl ZeroPod for #name` is checked eagerly against
+// `#name`'s actual fields, so it would reject the whole derive as soon as any field
+// (e.g. an enum, or a reference) isn't `ZeroPod` — regardless of whether `#name` is
+// ever used as an enum variant's payload field. If a derived type needs to be usable
+// as a payload field, add `unsafe impl typeinfo::ZeroPod for #name {}` by hand next
+// to the derive, once you've checked its all-zero bit pattern is actually valid.
+
+/// Picks the discriminant scalar type from the first `#[repr(..)]` item, in any
+/// of the type's attributes, that names an integer type, defaulting to `i32`
+/// when there is none.
+fn discriminant_type(attrs: &[Attribute]) -> Ident {
+    for attr in attrs {
+        if let MetaItem::List(ref ident, ref items) = attr.value {
+            if ident.as_ref() != "repr" {
+                continue;
+            }
+            for item in items {
+                if let MetaItem::Word(ref word) = *item {
+                    match word.as_ref() {
+                        "i8" | "i16" | "i32" | "i64" | "u8" | "u16" | "u32" | "u64" |
+                        "isize" | "usize" => return word.clone(),
+                        _ => {},
+                    }
+                }
+            }
+        }
+    }
+    Ident::new("i32")
+}
+
+fn type_info_impl(name: &Ident, attrs: &[Attribute], body: &Body) -> quote::Tokens {
     match *body {
         Body::Struct(VariantData::Unit) => {
-            quote! { ::typeinfo::Type::Compound(vec![], ty_size) }
+            quote! { ::typeinfo::Type::Compound(vec![], ty_size, ty_align) }
         },
         Body::Struct(VariantData::Struct(ref fs)) => {
             // duplicate iterators because of `quote!` limitations
@@ -47,14 +79,107 @@ fn type_info_impl(body: &Body) -> quote::Tokens {
                         &<#field_ty as ::typeinfo::TypeInfo>::type_info(),
                         stringify!(#field_name_1),
                         unsafe { &((*origin).#field_name_2) as *const _ as usize }
-                    )),*], ty_size)
+                    )),*], ty_size, ty_align)
             }
         },
         Body::Struct(VariantData::Tuple(_)) => {
             unimplemented!()
         },
-        Body::Enum(_) => {
-            unimplemented!()
+        Body::Enum(ref variants) => {
+            let disc_ty = discriminant_type(attrs);
+            let pushes: Vec<_> = variants.iter().map(|v| variant_push(name, v)).collect();
+            quote! {
+                let mut variants = Vec::<::typeinfo::Variant>::new();
+                let mut disc: i64 = 0;
+                #(#pushes)*
+                ::typeinfo::Type::Enum {
+                    variants: variants,
+                    discriminant: Box::new(<#disc_ty as ::typeinfo::TypeInfo>::type_info()),
+                    size: ty_size,
+                    align: ty_align,
+                }
+            }
+        },
+    }
+}
+
+/// Builds the statements that push a single `Variant` (with its discriminant and,
+/// for data-carrying variants, the offsets of its payload fields relative to an
+/// instance of that variant built from [`ZeroPod`](../typeinfo/trait.ZeroPod.html)
+/// placeholders) onto the `variants` vector.
+fn variant_push(name: &Ident, variant: &syn::Variant) -> quote::Tokens {
+    let v_name = &variant.ident;
+    let v_str = v_name.as_ref().to_string();
+    let set_disc = variant.discriminant.as_ref().map(|d| quote! { disc = (#d) as i64; });
+    match variant.data {
+        VariantData::Unit => {
+            quote! {
+                #set_disc
+                variants.push(::typeinfo::Variant::new(#v_str, disc, ::typeinfo::VariantFields::Unit));
+                disc += 1;
+            }
+        },
+        VariantData::Tuple(ref fs) => {
+            let binders: Vec<Ident> = (0..fs.len()).map(|i| Ident::new(format!("f{}", i))).collect();
+            let binders_bind = binders.clone();
+            let binders_use = binders.clone();
+            // duplicate iterators because of `quote!` limitations
+            let field_ty_new = fs.iter().map(|f| &f.ty);
+            let field_ty_info = fs.iter().map(|f| &f.ty);
+            quote! {
+                #set_disc
+                {
+                    let value: #name = #name::#v_name(
+                        #(::typeinfo::__zero_pod_placeholder::<#field_ty_new>()),*
+                    );
+                    let base = &value as *const _ as usize;
+                    let mut fields = Vec::<::typeinfo::Field>::new();
+                    match value {
+                        #name::#v_name(#(ref #binders_bind),*) => {
+                            #(
+                                fields.push(::typeinfo::Field::new(
+                                    &<#field_ty_info as ::typeinfo::TypeInfo>::type_info(),
+                                    (#binders_use as *const _ as usize) - base));
+                            )*
+                        },
+                        _ => unreachable!(),
+                    }
+                    variants.push(::typeinfo::Variant::new(#v_str, disc, ::typeinfo::VariantFields::Tuple(fields)));
+                }
+                disc += 1;
+            }
+        },
+        VariantData::Struct(ref fs) => {
+            // duplicate iterators because of `quote!` limitations
+            let field_name_1 = fs.iter().map(|f| &f.ident);
+            let field_name_2 = fs.iter().map(|f| &f.ident);
+            let field_name_3 = fs.iter().map(|f| &f.ident);
+            let field_name_4 = fs.iter().map(|f| &f.ident);
+            let field_ty_new = fs.iter().map(|f| &f.ty);
+            let field_ty_info = fs.iter().map(|f| &f.ty);
+            quote! {
+                #set_disc
+                {
+                    let value: #name = #name::#v_name {
+                        #(#field_name_1: ::typeinfo::__zero_pod_placeholder::<#field_ty_new>()),*
+                    };
+                    let base = &value as *const _ as usize;
+                    let mut fields = Vec::<::typeinfo::NamedField>::new();
+                    match value {
+                        #name::#v_name { #(ref #field_name_2),* } => {
+                            #(
+                                fields.push(::typeinfo::NamedField::new(
+                                    &<#field_ty_info as ::typeinfo::TypeInfo>::type_info(),
+                                    stringify!(#field_name_3),
+                                    (#field_name_4 as *const _ as usize) - base));
+                            )*
+                        },
+                        _ => unreachable!(),
+                    }
+                    variants.push(::typeinfo::Variant::new(#v_str, disc, ::typeinfo::VariantFields::Struct(fields)));
+                }
+                disc += 1;
+            }
         },
     }
 }
@@ -7,7 +7,7 @@ extern crate typeinfo_derive;
 use std::mem;
 
 use typeinfo::Type::*;
-use typeinfo::{TypeInfo, NamedField};
+use typeinfo::{TypeInfo, NamedField, VariantFields};
 
 #[test]
 fn test_compound_types() {
@@ -17,8 +17,10 @@ fn test_compound_types() {
     };
     let ty = X::type_info();
     assert_eq!(ty,
-               Compound(vec![NamedField::new(&Int32, "a", 0)], mem::size_of::<X>()));
+               Compound(vec![NamedField::new(&Int32, "a", 0)],
+                        mem::size_of::<X>(), mem::align_of::<X>()));
     assert_eq!(ty.size(), mem::size_of::<X>());
+    assert_eq!(ty.align(), mem::align_of::<X>());
     assert!(ty.is_compound());
 
     #[derive(Copy, Clone, TypeInfo)]
@@ -30,14 +32,60 @@ fn test_compound_types() {
     assert_eq!(ty,
                Compound(vec![NamedField::new(&UInt64, "a", 0),
                              NamedField::new(&Array(Box::new(X::type_info()), 2), "x", 8)],
-                        mem::size_of::<Y>()));
+                        mem::size_of::<Y>(), mem::align_of::<Y>()));
     assert_eq!(ty.size(), mem::size_of::<Y>());
+    assert_eq!(ty.align(), mem::align_of::<Y>());
     assert!(ty.is_compound());
 
     #[derive(Copy, Clone, TypeInfo)]
     struct Z;
     let ty = Z::type_info();
-    assert_eq!(ty, Compound(vec![], mem::size_of::<Z>()));
+    assert_eq!(ty, Compound(vec![], mem::size_of::<Z>(), mem::align_of::<Z>()));
     assert_eq!(ty.size(), mem::size_of::<Z>());
     assert!(ty.is_compound());
+}
+
+#[test]
+fn test_enum_types() {
+    #[repr(u8)]
+    #[derive(Copy, Clone, TypeInfo)]
+    enum Color {
+        Red,
+        Green = 5,
+        Blue,
+    }
+    let ty = Color::type_info();
+    if let Enum { ref variants, ref discriminant, size, align } = ty {
+        assert_eq!(**discriminant, UInt8);
+        assert_eq!(size, mem::size_of::<Color>());
+        assert_eq!(align, mem::align_of::<Color>());
+        assert_eq!(variants[0].discriminant, 0);
+        assert_eq!(variants[1].discriminant, 5);
+        assert_eq!(variants[2].discriminant, 6);
+    } else {
+        panic!("expected Type::Enum");
+    }
+    assert!(ty.is_enum());
+
+    #[repr(u8)]
+    #[derive(Copy, Clone, TypeInfo)]
+    enum Shape {
+        Point,
+        Circle(f32),
+        Rect { w: f32, h: f32 },
+    }
+    let ty = Shape::type_info();
+    if let Enum { ref variants, .. } = ty {
+        assert_eq!(variants[0].fields, VariantFields::Unit);
+        match variants[1].fields {
+            VariantFields::Tuple(ref fields) => assert_eq!(fields[0].ty, Float32),
+            _ => panic!("expected a tuple variant"),
+        }
+        match variants[2].fields {
+            VariantFields::Struct(ref fields) => assert_eq!(fields.len(), 2),
+            _ => panic!("expected a struct variant"),
+        }
+    } else {
+        panic!("expected Type::Enum");
+    }
 }
\ No newline at end of file